@@ -0,0 +1,81 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Deserialize;
+use tracing::warn;
+
+/// Service configuration, loaded from `shortener.toml` with environment
+/// variable overrides. Lets the service be deployed without recompiling,
+/// and replaces the previous reliance on the client-supplied `Host` header
+/// for building returned links.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Address the server listens on, e.g. `0.0.0.0:3366`.
+    pub listen_addr: String,
+    /// `sqlite://<path>`, `postgres://...`, or `memory`.
+    pub database_url: String,
+    /// Canonical base URL used to build returned short links, e.g.
+    /// `https://short.example.com` (no trailing slash; the returned link is
+    /// `<base_url>/<key>`). Falls back to the request's `Host`
+    /// header when unset.
+    pub base_url: Option<String>,
+    /// Named API keys accepted by `ApiKeyMiddleware`.
+    pub auth_tokens: HashMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            listen_addr: "0.0.0.0:3366".to_string(),
+            database_url: "sqlite://db.sqlite".to_string(),
+            base_url: None,
+            auth_tokens: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration from `shortener.toml` in the working directory,
+    /// applying environment variable overrides on top.
+    pub fn load() -> Self {
+        Self::load_from(Path::new("shortener.toml"))
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let mut config = match fs::read_to_string(path) {
+            Ok(contents) => match toml::from_str(&contents) {
+                Ok(config) => config,
+                Err(err) => {
+                    warn!(
+                        "failed to parse {}, falling back to defaults: {err}",
+                        path.display()
+                    );
+                    Config::default()
+                }
+            },
+            Err(_) => Config::default(),
+        };
+
+        config.apply_env_overrides();
+        config
+    }
+
+    fn apply_env_overrides(&mut self) {
+        if let Ok(value) = std::env::var("LISTEN_ADDR") {
+            self.listen_addr = value;
+        }
+        if let Ok(value) = std::env::var("DATABASE_URL") {
+            self.database_url = value;
+        }
+        if let Ok(value) = std::env::var("BASE_URL") {
+            self.base_url = Some(value);
+        }
+        if let Ok(value) = std::env::var("AUTH_TOKENS") {
+            self.auth_tokens = value
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(name, token)| (name.to_string(), token.to_string()))
+                .collect();
+        }
+    }
+}
@@ -1,6 +1,5 @@
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -16,56 +15,185 @@ use poem_openapi::{
     payload::{self, Json},
     Object, OpenApi, OpenApiService,
 };
+use rand::Rng;
 
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::FmtSubscriber;
 
-use lazy_static::lazy_static;
-
-use rusqlite::Connection;
-use utils::{format_radix::format_radix, json_error_middleware::JsonErrorMiddleware, raw_poem_response::RawPoemResponse};
+use config::Config;
+use metrics::Metrics;
+use storage::{LinkStats, MemoryStorage, PostgresStorage, SqliteStorage, Storage, StorageError};
+use utils::{
+    auth_middleware::ApiKeyMiddleware,
+    error::Error,
+    format_radix::format_radix,
+    json_error_middleware::JsonErrorMiddleware,
+    raw_poem_response::RawPoemResponse,
+    validation::{
+        assert_charset, assert_length, assert_no_control_chars, assert_not_reserved,
+        assert_url_scheme, Check, ValidationError,
+    },
+};
 
+mod config;
+mod metrics;
+mod storage;
 mod utils;
 
 #[derive(Debug, Deserialize, Object, Serialize)]
 struct UrlCreationRequest {
     pub url: String,
+    /// Caller-chosen key, instead of generating one. Must match
+    /// `[A-Za-z0-9_-]` and be between 3 and 32 characters long.
+    pub custom_alias: Option<String>,
+}
+
+/// How many generated keys to try in total before giving up. Only
+/// matters when two requests land in the same millisecond.
+const MAX_KEY_GENERATION_ATTEMPTS: u8 = 5;
+
+/// Top-level path segments registered ahead of `ShortenApi`'s `/:id` nest
+/// (see the `Route` built in `main`). A `custom_alias` matching one of
+/// these would be shadowed by the static route and could never redirect.
+const RESERVED_ALIASES: &[&str] = &["docs", "spec", "metrics", "admin"];
+
+impl Check for UrlCreationRequest {
+    fn check(&self) -> Result<(), ValidationError> {
+        let mut errors = ValidationError::new();
+
+        if let Err(msg) = assert_length(
+            &self.url,
+            1,
+            2048,
+            "url must be between 1 and 2048 characters",
+        ) {
+            errors.push("url", msg);
+        }
+        if let Err(msg) = assert_url_scheme(&self.url, "url must use the http or https scheme") {
+            errors.push("url", msg);
+        }
+        if let Err(msg) =
+            assert_no_control_chars(&self.url, "url must not contain control characters")
+        {
+            errors.push("url", msg);
+        }
+
+        if let Some(alias) = &self.custom_alias {
+            if let Err(msg) = assert_length(
+                alias,
+                3,
+                32,
+                "custom_alias must be between 3 and 32 characters",
+            ) {
+                errors.push("custom_alias", msg);
+            }
+            if let Err(msg) = assert_charset(
+                alias,
+                |c| c.is_ascii_alphanumeric() || c == '_' || c == '-',
+                "custom_alias may only contain letters, digits, '_' and '-'",
+            ) {
+                errors.push("custom_alias", msg);
+            }
+            if let Err(msg) = assert_not_reserved(
+                alias,
+                RESERVED_ALIASES,
+                "custom_alias is reserved for the API's own routes",
+            ) {
+                errors.push("custom_alias", msg);
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+/// Combine the current timestamp with a small random suffix so two
+/// requests in the same millisecond are unlikely to collide.
+fn generate_key() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    let suffix: u32 = rand::thread_rng().gen_range(0..36u32.pow(3));
+
+    format!(
+        "{}{}",
+        format_radix(millis, 36),
+        format_radix(suffix as u128, 36)
+    )
+}
+
+/// Generate a key and insert `url` under it, generating a fresh key on
+/// each collision and giving up after `MAX_KEY_GENERATION_ATTEMPTS` keys.
+async fn insert_with_generated_key(
+    storage: &dyn Storage,
+    url: &str,
+) -> utils::error::Result<String> {
+    for _ in 0..MAX_KEY_GENERATION_ATTEMPTS {
+        let candidate = generate_key();
+        match storage.insert(&candidate, url).await {
+            Ok(()) => return Ok(candidate),
+            Err(StorageError::Conflict) => continue,
+            Err(err) => return Err(Error::from(err)),
+        }
+    }
+
+    Err(Error::KeyGenerationFailed(
+        "could not generate a unique key".to_string(),
+    ))
+}
+
+#[derive(Debug, Object, Serialize)]
+struct LinkStatsResponse {
+    hits: i64,
+    created_at: i64,
+    last_hit_at: Option<i64>,
 }
 
-lazy_static! {
-    static ref DATA: Arc<Mutex<HashMap<String, String>>> = Arc::new(Mutex::new(HashMap::new()));
-    static ref SQLITE: Arc<Mutex<Connection>> = Arc::new(Mutex::new(
-        Connection::open("db.sqlite").expect("can't open sqlite database")
-    ));
+impl From<LinkStats> for LinkStatsResponse {
+    fn from(stats: LinkStats) -> Self {
+        Self {
+            hits: stats.hits,
+            created_at: stats.created_at,
+            last_hit_at: stats.last_hit_at,
+        }
+    }
 }
 
 struct ShortenApi {
     nested_path: String,
+    /// See [`Config::base_url`].
+    base_url: Option<String>,
+    storage: Arc<dyn Storage>,
+    metrics: Arc<Metrics>,
 }
 
 #[OpenApi]
 impl ShortenApi {
     /// Get item uri from shorthand
     #[oai(path = "/:id", method = "get")]
-    async fn get_url(&self, id: Path<String>) -> RawPoemResponse {
-        let sqlite = SQLITE.clone();
-        let conn = sqlite.lock().unwrap();
-
-        let mut stmt = conn
-            .prepare("SELECT key, uri FROM links WHERE key = ? ;")
-            .unwrap();
-        let mut result = stmt.query([id.0]).unwrap();
-
-        match result.next().unwrap() {
-            Some(uri) => RawPoemResponse(
-                Response::builder()
-                    .status(StatusCode::MOVED_PERMANENTLY)
-                    .header("Location", uri.get::<usize, String>(1).unwrap())
-                    .finish(),
-            ),
-            None => RawPoemResponse(Response::builder().status(StatusCode::NOT_FOUND).finish()),
+    async fn get_url(&self, id: Path<String>) -> poem::Result<RawPoemResponse> {
+        match self.storage.lookup(&id.0).await.map_err(Error::from)? {
+            Some(uri) => {
+                // The redirect itself already succeeded; a hiccup recording
+                // analytics for it shouldn't turn a working redirect into a
+                // 500 for the end user.
+                if let Err(err) = self.storage.record_hit(&id.0).await {
+                    warn!("failed to record hit for {}: {err}", id.0);
+                }
+                self.metrics.record_redirect();
+                Ok(RawPoemResponse(
+                    Response::builder()
+                        .status(StatusCode::MOVED_PERMANENTLY)
+                        .header("Location", uri)
+                        .finish(),
+                ))
+            }
+            None => Ok(RawPoemResponse(
+                Response::builder().status(StatusCode::NOT_FOUND).finish(),
+            )),
         }
     }
 
@@ -74,48 +202,79 @@ impl ShortenApi {
         &self,
         host: param::Header<String>,
         input: payload::Json<UrlCreationRequest>,
-    ) -> Json<serde_json::Value> {
-        let key = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis();
+    ) -> poem::Result<Json<serde_json::Value>> {
+        input.check().map_err(Error::from)?;
 
-        let key = format_radix(key, 36);
+        let key = match &input.custom_alias {
+            Some(alias) => match self.storage.insert(alias, &input.url).await {
+                Ok(()) => alias.clone(),
+                Err(StorageError::Conflict) => {
+                    return Err(Error::Conflict(format!(
+                        "custom_alias '{alias}' is already in use"
+                    ))
+                    .into())
+                }
+                Err(err) => return Err(Error::from(err).into()),
+            },
+            None => insert_with_generated_key(self.storage.as_ref(), &input.url).await?,
+        };
 
-        let sqlite = SQLITE.clone();
-        let conn = sqlite.lock().unwrap();
+        self.metrics.record_link_created();
 
-        conn.execute(
-            "INSERT INTO links (key, uri) VALUES (?1, ?2)",
-            (&key, &input.url),
-        )
-        .unwrap();
+        let base = self.base_url.clone().unwrap_or(host.0);
 
-        Json(json!({
-            "url": format!("{}{}{}", host.0,self.nested_path, key)
-        }))
+        Ok(Json(json!({
+            "url": format!("{}{}{}", base, self.nested_path, key)
+        })))
     }
 }
 
+/// Operator-facing API, kept separate from `ShortenApi` and mounted under
+/// `/admin` so link-management traffic never shares a surface with
+/// untrusted redirect/creation traffic.
+struct AdminApi {
+    storage: Arc<dyn Storage>,
+}
+
+#[OpenApi]
+impl AdminApi {
+    /// Get hit-count and timing stats for a short link
+    #[oai(path = "/stats/:id", method = "get")]
+    async fn stats(&self, id: Path<String>) -> poem::Result<Json<serde_json::Value>> {
+        match self.storage.stats(&id.0).await.map_err(Error::from)? {
+            Some(stats) => Ok(Json(json!(LinkStatsResponse::from(stats)))),
+            None => Err(Error::NotFound("link").into()),
+        }
+    }
+}
+
+/// Build the configured storage backend from `database_url`: `memory`,
+/// `sqlite://<path>`, or `postgres://...`.
+async fn build_storage(database_url: &str) -> Arc<dyn Storage> {
+    if database_url == "memory" {
+        return Arc::new(MemoryStorage::new());
+    }
+
+    if let Some(conn_str) = database_url.strip_prefix("postgres://") {
+        return Arc::new(
+            PostgresStorage::connect(&format!("postgres://{conn_str}"))
+                .await
+                .expect("can't connect to postgres"),
+        );
+    }
+
+    let path = database_url
+        .strip_prefix("sqlite://")
+        .unwrap_or(database_url);
+    Arc::new(SqliteStorage::open(path).expect("can't open sqlite database"))
+}
+
 #[tokio::main]
-async fn main() -> Result<(), std::io::Error> {
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     if std::env::var_os("RUST_LOG").is_none() {
         std::env::set_var("RUST_LOG", "poem=debug");
     }
 
-    {
-        // just drop if can't initialize database
-        let con = SQLITE.lock().unwrap();
-        con.execute(
-            "CREATE TABLE IF NOT EXISTS links (
-                key   TEXT PRIMARY KEY,
-                uri   TEXT NOT NULL
-            )",
-            (),
-        )
-        .unwrap();
-    }
-
     // tracing_subscriber::fmt::init();
     let subscriber = FmtSubscriber::builder()
         .pretty()
@@ -123,9 +282,20 @@ async fn main() -> Result<(), std::io::Error> {
         .finish();
 
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+
+    let config = Config::load();
+
+    let storage = build_storage(&config.database_url).await;
+    storage.init().await?;
+    let metrics = Arc::new(Metrics::default());
+    let auth_middleware = ApiKeyMiddleware::new(config.auth_tokens.clone());
+
     let api_service = OpenApiService::new(
         ShortenApi {
             nested_path: '/'.to_string(),
+            base_url: config.base_url.clone(),
+            storage: storage.clone(),
+            metrics: metrics.clone(),
         },
         "Link shortener",
         "1.0",
@@ -133,14 +303,25 @@ async fn main() -> Result<(), std::io::Error> {
     let ui = api_service.swagger_ui();
     let spec = api_service.spec();
 
-    Server::new(TcpListener::bind("0.0.0.0:3366"))
+    let admin_service = OpenApiService::new(AdminApi { storage }, "Link shortener admin", "1.0");
+
+    let metrics_for_endpoint = metrics.clone();
+
+    Server::new(TcpListener::bind(config.listen_addr.as_str()))
         .run_with_graceful_shutdown(
             Route::new()
                 .nest("/docs", ui)
                 .at("/spec", poem::endpoint::make_sync(move |_| spec.clone()))
+                .at(
+                    "/metrics",
+                    poem::endpoint::make_sync(move |_| metrics_for_endpoint.render()),
+                )
+                .nest("/admin", admin_service)
                 .nest(
                     "/",
-                    api_service.with(Compression::new().with_quality(CompressionLevel::Best)),
+                    api_service
+                        .with(Compression::new().with_quality(CompressionLevel::Best))
+                        .with(auth_middleware),
                 )
                 .with(Tracing)
                 .with(JsonErrorMiddleware),
@@ -155,3 +336,107 @@ async fn main() -> Result<(), std::io::Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn check_rejects_custom_alias_matching_a_reserved_route() {
+        let request = UrlCreationRequest {
+            url: "http://example.com".to_string(),
+            custom_alias: Some("admin".to_string()),
+        };
+        let errors = request.check().unwrap_err();
+        assert!(errors
+            .0
+            .iter()
+            .any(|field_err| field_err.field == "custom_alias"));
+    }
+
+    #[test]
+    fn generate_key_is_nonempty_base36() {
+        let key = generate_key();
+        assert!(!key.is_empty());
+        assert!(key.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    #[test]
+    fn generate_key_varies_across_calls() {
+        let a = generate_key();
+        let b = generate_key();
+        assert_ne!(a, b);
+    }
+
+    /// A [`Storage`] stub whose first `conflicts_left` calls to `insert`
+    /// report a conflict; later calls succeed.
+    struct ConflictNTimesStorage {
+        conflicts_left: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Storage for ConflictNTimesStorage {
+        async fn init(&self) -> storage::Result<()> {
+            Ok(())
+        }
+
+        async fn insert(&self, _key: &str, _uri: &str) -> storage::Result<()> {
+            if self.conflicts_left.load(Ordering::SeqCst) > 0 {
+                self.conflicts_left.fetch_sub(1, Ordering::SeqCst);
+                Err(StorageError::Conflict)
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn lookup(&self, _key: &str) -> storage::Result<Option<String>> {
+            Ok(None)
+        }
+
+        async fn record_hit(&self, _key: &str) -> storage::Result<()> {
+            Ok(())
+        }
+
+        async fn stats(&self, _key: &str) -> storage::Result<Option<LinkStats>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn insert_with_generated_key_retries_past_collisions() {
+        let storage = ConflictNTimesStorage {
+            conflicts_left: AtomicUsize::new((MAX_KEY_GENERATION_ATTEMPTS - 1) as usize),
+        };
+
+        let key = insert_with_generated_key(&storage, "http://example.com")
+            .await
+            .expect("should succeed within the retry budget");
+        assert!(!key.is_empty());
+    }
+
+    #[tokio::test]
+    async fn insert_with_generated_key_gives_up_after_max_attempts() {
+        let storage = ConflictNTimesStorage {
+            conflicts_left: AtomicUsize::new(MAX_KEY_GENERATION_ATTEMPTS as usize),
+        };
+
+        let err = insert_with_generated_key(&storage, "http://example.com")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::KeyGenerationFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn insert_with_generated_key_inserts_into_real_storage() {
+        let storage = MemoryStorage::new();
+        let key = insert_with_generated_key(&storage, "http://example.com")
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.lookup(&key).await.unwrap(),
+            Some("http://example.com".to_string())
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Process-wide Prometheus-style counters, separate from the per-link hit
+/// counts kept in storage.
+#[derive(Default)]
+pub struct Metrics {
+    redirects_total: AtomicU64,
+    links_created_total: AtomicU64,
+}
+
+impl Metrics {
+    pub fn record_redirect(&self) {
+        self.redirects_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_link_created(&self) {
+        self.links_created_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "# HELP shortener_redirects_total Total number of successful redirects served.\n\
+             # TYPE shortener_redirects_total counter\n\
+             shortener_redirects_total {}\n\
+             # HELP shortener_links_created_total Total number of short links created.\n\
+             # TYPE shortener_links_created_total counter\n\
+             shortener_links_created_total {}\n",
+            self.redirects_total.load(Ordering::Relaxed),
+            self.links_created_total.load(Ordering::Relaxed),
+        )
+    }
+}
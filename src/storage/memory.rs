@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use super::{LinkStats, Result, Storage, StorageError};
+
+struct LinkRecord {
+    uri: String,
+    hits: i64,
+    created_at: i64,
+    last_hit_at: Option<i64>,
+}
+
+/// `HashMap`-backed storage, mainly useful for tests and local development
+/// where spinning up a real database isn't worth it. Each instance owns its
+/// own map, so separate `MemoryStorage`s (e.g. in different tests) never
+/// see each other's data.
+pub struct MemoryStorage {
+    data: Arc<Mutex<HashMap<String, LinkRecord>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn insert(&self, key: &str, uri: &str) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if data.contains_key(key) {
+            return Err(StorageError::Conflict);
+        }
+        data.insert(
+            key.to_string(),
+            LinkRecord {
+                uri: uri.to_string(),
+                hits: 0,
+                created_at: now(),
+                last_hit_at: None,
+            },
+        );
+        Ok(())
+    }
+
+    async fn lookup(&self, key: &str) -> Result<Option<String>> {
+        let data = self.data.lock().await;
+        Ok(data.get(key).map(|record| record.uri.clone()))
+    }
+
+    async fn record_hit(&self, key: &str) -> Result<()> {
+        let mut data = self.data.lock().await;
+        if let Some(record) = data.get_mut(key) {
+            record.hits += 1;
+            record.last_hit_at = Some(now());
+        }
+        Ok(())
+    }
+
+    async fn stats(&self, key: &str) -> Result<Option<LinkStats>> {
+        let data = self.data.lock().await;
+        Ok(data.get(key).map(|record| LinkStats {
+            hits: record.hits,
+            created_at: record.created_at,
+            last_hit_at: record.last_hit_at,
+        }))
+    }
+}
@@ -0,0 +1,60 @@
+mod memory;
+mod postgres;
+mod sqlite;
+
+pub use memory::MemoryStorage;
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+/// Errors that can occur while talking to a [`Storage`] backend.
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("postgres error: {0}")]
+    Postgres(#[from] tokio_postgres::Error),
+
+    #[error("storage connection lock poisoned")]
+    LockPoisoned,
+
+    #[error("key already exists")]
+    Conflict,
+}
+
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+/// Hit count and timing information for a single short link, as recorded
+/// by a [`Storage`] backend.
+#[derive(Debug, Clone)]
+pub struct LinkStats {
+    pub hits: i64,
+    pub created_at: i64,
+    pub last_hit_at: Option<i64>,
+}
+
+/// Abstracts over the place short links are actually persisted, so
+/// `ShortenApi` doesn't need to know whether it's talking to SQLite,
+/// Postgres, or an in-memory map.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Create the backing schema if it doesn't exist yet.
+    async fn init(&self) -> Result<()>;
+
+    /// Store `uri` under `key`. Returns [`StorageError::Conflict`] if `key`
+    /// is already taken.
+    async fn insert(&self, key: &str, uri: &str) -> Result<()>;
+
+    /// Look up the URI stored under `key`, if any.
+    async fn lookup(&self, key: &str) -> Result<Option<String>>;
+
+    /// Record a redirect for `key`, bumping its hit count and last-access
+    /// timestamp.
+    async fn record_hit(&self, key: &str) -> Result<()>;
+
+    /// Fetch hit/timing stats for `key`, if it exists.
+    async fn stats(&self, key: &str) -> Result<Option<LinkStats>>;
+}
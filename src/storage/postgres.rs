@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use tokio_postgres::{Client, NoTls};
+use tracing::error;
+
+use super::{LinkStats, Result, Storage, StorageError};
+
+/// Postgres-backed storage, for deployments that want a real database
+/// server instead of a local SQLite file.
+pub struct PostgresStorage {
+    client: Client,
+}
+
+impl PostgresStorage {
+    pub async fn connect(conn_str: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(conn_str, NoTls).await?;
+
+        // The connection object performs the actual IO and must be driven
+        // to completion on its own task, or queries below will hang forever.
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                error!("postgres connection error: {err}");
+            }
+        });
+
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    async fn init(&self) -> Result<()> {
+        self.client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS links (
+                    key           TEXT PRIMARY KEY,
+                    uri           TEXT NOT NULL,
+                    hits          BIGINT NOT NULL DEFAULT 0,
+                    created_at    BIGINT NOT NULL,
+                    last_hit_at   BIGINT
+                )",
+                &[],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn insert(&self, key: &str, uri: &str) -> Result<()> {
+        let inserted = self
+            .client
+            .execute(
+                "INSERT INTO links (key, uri, created_at) VALUES ($1, $2, extract(epoch from now())::bigint)",
+                &[&key, &uri],
+            )
+            .await;
+
+        match inserted {
+            Ok(_) => Ok(()),
+            Err(err) if err.code() == Some(&tokio_postgres::error::SqlState::UNIQUE_VIOLATION) => {
+                Err(StorageError::Conflict)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn lookup(&self, key: &str) -> Result<Option<String>> {
+        let row = self
+            .client
+            .query_opt("SELECT uri FROM links WHERE key = $1", &[&key])
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    async fn record_hit(&self, key: &str) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE links SET hits = hits + 1, last_hit_at = extract(epoch from now())::bigint WHERE key = $1",
+                &[&key],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn stats(&self, key: &str) -> Result<Option<LinkStats>> {
+        let row = self
+            .client
+            .query_opt(
+                "SELECT hits, created_at, last_hit_at FROM links WHERE key = $1",
+                &[&key],
+            )
+            .await?;
+
+        Ok(row.map(|row| LinkStats {
+            hits: row.get(0),
+            created_at: row.get(1),
+            last_hit_at: row.get(2),
+        }))
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use rusqlite::{Connection, OptionalExtension};
+
+use super::{LinkStats, Result, Storage, StorageError};
+
+/// SQLite-backed storage. Keeps the `links (key, uri)` table the service
+/// has always used and adds hit-count and timing columns to it; the
+/// connection is still serialized behind a `Mutex` since `rusqlite` is
+/// synchronous.
+pub struct SqliteStorage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn init(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|_| StorageError::LockPoisoned)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS links (
+                key           TEXT PRIMARY KEY,
+                uri           TEXT NOT NULL,
+                hits          INTEGER NOT NULL DEFAULT 0,
+                created_at    INTEGER NOT NULL,
+                last_hit_at   INTEGER
+            )",
+            (),
+        )?;
+        Ok(())
+    }
+
+    async fn insert(&self, key: &str, uri: &str) -> Result<()> {
+        let conn = self.conn.lock().map_err(|_| StorageError::LockPoisoned)?;
+        let inserted = conn.execute(
+            "INSERT INTO links (key, uri, created_at) VALUES (?1, ?2, strftime('%s', 'now'))",
+            (key, uri),
+        );
+
+        match inserted {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(err, _))
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                Err(StorageError::Conflict)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn lookup(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().map_err(|_| StorageError::LockPoisoned)?;
+        let mut stmt = conn.prepare("SELECT uri FROM links WHERE key = ?1")?;
+        let mut rows = stmt.query([key])?;
+
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn record_hit(&self, key: &str) -> Result<()> {
+        let conn = self.conn.lock().map_err(|_| StorageError::LockPoisoned)?;
+        conn.execute(
+            "UPDATE links SET hits = hits + 1, last_hit_at = strftime('%s', 'now') WHERE key = ?1",
+            [key],
+        )?;
+        Ok(())
+    }
+
+    async fn stats(&self, key: &str) -> Result<Option<LinkStats>> {
+        let conn = self.conn.lock().map_err(|_| StorageError::LockPoisoned)?;
+        let mut stmt =
+            conn.prepare("SELECT hits, created_at, last_hit_at FROM links WHERE key = ?1")?;
+
+        stmt.query_row([key], |row| {
+            Ok(LinkStats {
+                hits: row.get(0)?,
+                created_at: row.get(1)?,
+                last_hit_at: row.get(2)?,
+            })
+        })
+        .optional()
+        .map_err(Into::into)
+    }
+}
@@ -0,0 +1,100 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use poem::{
+    http::StatusCode, Endpoint, Error, IntoResponse, Method, Middleware, Request, Response, Result,
+};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Gates link creation behind an `Authorization: Bearer <token>` header.
+///
+/// Tokens are kept hashed in memory and compared in constant time so a
+/// timing attack can't be used to recover a valid token byte-by-byte.
+/// Redirect lookups (`GET /:id`) are left untouched; only `POST /`
+/// requests are checked.
+#[derive(Clone)]
+pub struct ApiKeyMiddleware {
+    // sha256(token) -> name, so usage can be attributed per-key later.
+    tokens: Arc<HashMap<[u8; 32], String>>,
+}
+
+impl ApiKeyMiddleware {
+    pub fn new<I, K, V>(tokens: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: AsRef<str>,
+    {
+        let tokens = tokens
+            .into_iter()
+            .map(|(name, token)| (hash_token(token.as_ref()), name.into()))
+            .collect();
+
+        Self {
+            tokens: Arc::new(tokens),
+        }
+    }
+
+    fn name_for(&self, token: &str) -> Option<&str> {
+        let candidate = hash_token(token);
+        self.tokens
+            .iter()
+            .find(|(known, _)| bool::from(known.ct_eq(&candidate)))
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+fn hash_token(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+impl<E: Endpoint> Middleware<E> for ApiKeyMiddleware {
+    type Output = ApiKeyMiddlewareImpl<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ApiKeyMiddlewareImpl {
+            ep,
+            mw: self.clone(),
+        }
+    }
+}
+
+pub struct ApiKeyMiddlewareImpl<E> {
+    ep: E,
+    mw: ApiKeyMiddleware,
+}
+
+#[async_trait]
+impl<E: Endpoint> Endpoint for ApiKeyMiddlewareImpl<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        // Only creation is gated; redirects stay public.
+        if req.method() != Method::POST {
+            return self.ep.call(req).await.map(IntoResponse::into_response);
+        }
+
+        let token = req
+            .headers()
+            .get(poem::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match token.and_then(|token| self.mw.name_for(token)) {
+            Some(name) => {
+                let mut req = req;
+                req.extensions_mut()
+                    .insert(ApiKeyIdentity(name.to_string()));
+                self.ep.call(req).await.map(IntoResponse::into_response)
+            }
+            None => Err(Error::from_status(StatusCode::UNAUTHORIZED)),
+        }
+    }
+}
+
+/// The name of the API key that authenticated the current request, stashed
+/// in the request extensions for downstream handlers that want to attribute
+/// usage per-key.
+#[derive(Debug, Clone)]
+pub struct ApiKeyIdentity(pub String);
@@ -0,0 +1,51 @@
+use poem::http::StatusCode;
+use thiserror::Error;
+
+use crate::storage::StorageError;
+
+use super::validation::ValidationError;
+
+/// The crate-wide error type. Every fallible operation that can reach a
+/// handler flows through here so `JsonErrorMiddleware` always renders a
+/// consistent JSON body with the right status code, instead of the
+/// service panicking on the first hiccup.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error("{0} not found")]
+    NotFound(&'static str),
+
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("{0}")]
+    KeyGenerationFailed(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::Conflict(_) | Error::Storage(StorageError::Conflict) => StatusCode::CONFLICT,
+            Error::KeyGenerationFailed(_) | Error::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<Error> for poem::Error {
+    fn from(err: Error) -> Self {
+        let status = err.status();
+        // `Error::new` (rather than `from_string`) keeps `err` around as
+        // the source, so `JsonErrorMiddleware` can downcast back to it and
+        // render e.g. `Error::Validation`'s field list as real JSON.
+        poem::Error::new(err, status)
+    }
+}
@@ -1,4 +1,3 @@
-
 pub fn format_radix(mut x: u128, radix: u32) -> String {
     let mut result = vec![];
     let radix = radix.min(36).max(2);
@@ -13,4 +12,4 @@ pub fn format_radix(mut x: u128, radix: u32) -> String {
         }
     }
     result.into_iter().rev().collect()
-}
\ No newline at end of file
+}
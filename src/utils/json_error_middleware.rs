@@ -3,6 +3,8 @@ use async_trait::async_trait;
 use poem::{Endpoint, IntoResponse, Middleware, Request, Response};
 use poem_openapi::payload::Json;
 
+use super::error::Error;
+
 pub struct JsonErrorMiddleware;
 
 impl<E: Endpoint> Middleware<E> for JsonErrorMiddleware {
@@ -24,11 +26,27 @@ impl<E: Endpoint> Endpoint for JsonErrorMiddlewareImpl<E> {
 
         match res {
             Ok(resp) => Ok(resp.into_response()),
-            Err(err) => Ok(Json(
-                serde_json::json!({"error": err.to_string(),"isError": true,"statusCode": err.status().as_u16()}),
-            )
-            .with_status(err.status())
-            .into_response()),
+            Err(err) => {
+                let status = err.status();
+
+                // Validation failures carry a structured field list; surface
+                // it as real JSON instead of making the client parse it back
+                // out of the `error` string.
+                let body = match err.downcast_ref::<Error>() {
+                    Some(Error::Validation(validation_err)) => serde_json::json!({
+                        "isError": true,
+                        "statusCode": status.as_u16(),
+                        "errors": validation_err.0.iter().map(|field_err| {
+                            serde_json::json!({"field": field_err.field, "message": field_err.message})
+                        }).collect::<Vec<_>>(),
+                    }),
+                    _ => {
+                        serde_json::json!({"error": err.to_string(), "isError": true, "statusCode": status.as_u16()})
+                    }
+                };
+
+                Ok(Json(body).with_status(status).into_response())
+            }
         }
     }
 }
@@ -0,0 +1,6 @@
+pub mod auth_middleware;
+pub mod error;
+pub mod format_radix;
+pub mod json_error_middleware;
+pub mod raw_poem_response;
+pub mod validation;
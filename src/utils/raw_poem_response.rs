@@ -29,4 +29,4 @@ impl ApiResponse for RawPoemResponse {
     }
 
     fn register(_: &mut Registry) {}
-}
\ No newline at end of file
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+/// A single field that failed validation, and why.
+#[derive(Debug)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// The set of field errors collected while checking a request.
+/// `JsonErrorMiddleware` renders it as an `errors` array so callers get a
+/// machine-readable list of which field failed and why; its `Display` form
+/// is the same list as a JSON string.
+#[derive(Debug, Default)]
+pub struct ValidationError(pub Vec<FieldError>);
+
+impl ValidationError {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, field: &'static str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field,
+            message: message.into(),
+        });
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<_> = self
+            .0
+            .iter()
+            .map(|e| serde_json::json!({"field": e.field, "message": e.message}))
+            .collect();
+        write!(f, "{}", serde_json::Value::Array(fields))
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Implemented by request types that need structured validation before
+/// touching storage.
+pub trait Check {
+    fn check(&self) -> Result<(), ValidationError>;
+}
+
+pub fn assert_length(value: &str, min: usize, max: usize, msg: &str) -> Result<(), String> {
+    if value.len() < min || value.len() > max {
+        Err(msg.to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn assert_url_scheme(value: &str, msg: &str) -> Result<(), String> {
+    // Scheme is case-insensitive per RFC 3986, so `HTTP://` is as valid as
+    // `http://`.
+    let lower = value.to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(msg.to_string())
+    }
+}
+
+/// Rejects control characters (including `\r`/`\n`), so a validated value
+/// can't be used to smuggle extra header lines into a response built from
+/// it (e.g. a `Location` header via `RawPoemResponse`).
+pub fn assert_no_control_chars(value: &str, msg: &str) -> Result<(), String> {
+    if value.chars().any(|c| c.is_control()) {
+        Err(msg.to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn assert_not_reserved(value: &str, reserved: &[&str], msg: &str) -> Result<(), String> {
+    if reserved.contains(&value) {
+        Err(msg.to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn assert_charset(
+    value: &str,
+    allowed: impl Fn(char) -> bool,
+    msg: &str,
+) -> Result<(), String> {
+    if value.chars().all(allowed) {
+        Ok(())
+    } else {
+        Err(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_length_rejects_out_of_bounds() {
+        assert!(assert_length("ab", 3, 10, "too short").is_err());
+        assert!(assert_length("abcdefghijk", 3, 10, "too long").is_err());
+        assert!(assert_length("abc", 3, 10, "ok").is_ok());
+    }
+
+    #[test]
+    fn assert_url_scheme_requires_http_or_https() {
+        assert!(assert_url_scheme("http://example.com", "bad scheme").is_ok());
+        assert!(assert_url_scheme("https://example.com", "bad scheme").is_ok());
+        assert!(assert_url_scheme("javascript:alert(1)", "bad scheme").is_err());
+        assert!(assert_url_scheme("data:text/html,<script>", "bad scheme").is_err());
+    }
+
+    #[test]
+    fn assert_url_scheme_is_case_insensitive() {
+        assert!(assert_url_scheme("HTTP://example.com", "bad scheme").is_ok());
+        assert!(assert_url_scheme("HTTPS://example.com", "bad scheme").is_ok());
+        assert!(assert_url_scheme("HtTp://example.com", "bad scheme").is_ok());
+    }
+
+    #[test]
+    fn assert_no_control_chars_rejects_crlf_injection() {
+        assert!(assert_no_control_chars("http://example.com", "has control chars").is_ok());
+        assert!(
+            assert_no_control_chars("http://x\r\nSet-Cookie: evil", "has control chars").is_err()
+        );
+        assert!(assert_no_control_chars("http://x\n", "has control chars").is_err());
+    }
+
+    #[test]
+    fn assert_not_reserved_rejects_exact_matches_only() {
+        assert!(assert_not_reserved("admin", &["admin", "docs"], "reserved").is_err());
+        assert!(assert_not_reserved("administrator", &["admin", "docs"], "reserved").is_ok());
+        assert!(assert_not_reserved("my-link", &["admin", "docs"], "reserved").is_ok());
+    }
+
+    #[test]
+    fn assert_charset_checks_every_char() {
+        let allowed = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        assert!(assert_charset("abc-123_XYZ", allowed, "bad charset").is_ok());
+        assert!(assert_charset("abc/123", allowed, "bad charset").is_err());
+    }
+
+    #[test]
+    fn validation_error_into_result_roundtrips() {
+        let errors = ValidationError::new();
+        assert!(errors.into_result().is_ok());
+
+        let mut errors = ValidationError::new();
+        errors.push("url", "bad");
+        assert!(errors.into_result().is_err());
+    }
+}